@@ -4,15 +4,38 @@
 //! Single-file, made for learning / teaching / example purposes.
 //!
 
+// anchor_lang::error::Error carries an ErrorCode plus optional compared-values
+// metadata, so it's unavoidably >160 bytes; the handlers and most helpers here
+// return it by value like the rest of the Anchor ecosystem does.
+#![allow(clippy::result_large_err)]
+
 use anchor_lang::prelude::*;
 use solana_program::instruction::Instruction;
 use solana_program::sysvar::instructions::{ID as IX_ID, load_instruction_at_checked};
 use solana_program::ed25519_program::{ID as ED25519_ID};
+use solana_program::secp256k1_program::{ID as SECP256K1_ID};
+
+use bytemuck::{Pod, Zeroable};
 
 use std::convert::TryInto;
 
 declare_id!("DHxesXA69rUmz5AJ1CnLCQezUzQR5j7KKTwTp1zZPc9j");
 
+/// The 14-byte offsets header the Ed25519Program prepends to each
+/// signature it describes, parsed zero-copy instead of by hand so a
+/// malformed/truncated instruction can't panic before the checks run.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct Ed25519SignatureOffsets {
+    pub signature_offset: u16,
+    pub signature_instruction_index: u16,
+    pub public_key_offset: u16,
+    pub public_key_instruction_index: u16,
+    pub message_data_offset: u16,
+    pub message_data_size: u16,
+    pub message_instruction_index: u16,
+}
+
 /// Main module
 #[program]
 pub mod signatures {
@@ -21,13 +44,64 @@ pub mod signatures {
     /// External instruction that only gets executed if
     /// an `Ed25519Program.createInstructionWithPublicKey`
     /// instruction was sent in the same transaction.
-    pub fn verify(ctx: Context<Verify>, msg: Vec<u8>, sig: [u8; 64], pubkey: [u8; 32]) -> ProgramResult {        
-        
+    pub fn verify(ctx: Context<Verify>, msg: Vec<u8>, sig: [u8; 64], pubkey: [u8; 32]) -> Result<()> {
+
+        // Get what should be the Ed25519Program instruction
+        let ix: Instruction = load_instruction_at_checked(0, &ctx.accounts.ix_sysvar)?;
+
+        // Check that ix is what we expect to have been sent
+        utils::sig_verify(&ctx.accounts.ix_sysvar, &ix, &msg, &sig, &pubkey)?;
+
+        // Do other stuff
+
+        Ok(())
+    }
+
+    /// Same as `verify`, but accepts an Ed25519Program instruction that
+    /// carries more than one signature, as is supported by the native
+    /// Ed25519 program (up to 255 signatures packed into one instruction).
+    pub fn verify_many(ctx: Context<Verify>, sigs: Vec<(Vec<u8>, [u8; 64], [u8; 32])>) -> Result<()> {
+
         // Get what should be the Ed25519Program instruction
         let ix: Instruction = load_instruction_at_checked(0, &ctx.accounts.ix_sysvar)?;
 
         // Check that ix is what we expect to have been sent
-        utils::sig_verify(&ix, &msg, &sig, &pubkey)?;
+        utils::sig_verify_many(&ctx.accounts.ix_sysvar, &ix, &sigs)?;
+
+        // Do other stuff
+
+        Ok(())
+    }
+
+    /// External instruction that only gets executed if a
+    /// `Secp256k1Program.createInstructionWithEthAddress`
+    /// instruction was sent in the same transaction. Lets a caller
+    /// prove ownership of an Ethereum address via an ECDSA signature.
+    pub fn verify_eth(ctx: Context<Verify>, msg: Vec<u8>, sig: [u8; 65], eth_address: [u8; 20]) -> Result<()> {
+
+        // Get what should be the Secp256k1Program instruction
+        let ix: Instruction = load_instruction_at_checked(0, &ctx.accounts.ix_sysvar)?;
+
+        // Check that ix is what we expect to have been sent
+        utils::secp256k1_verify(&ctx.accounts.ix_sysvar, &ix, &msg, &sig, &eth_address)?;
+
+        // Do other stuff
+
+        Ok(())
+    }
+
+    /// External instruction that only gets executed if the introspected
+    /// Ed25519Program instruction carries at least `threshold` distinct
+    /// signatures over `message`, each from a member of `allowed`. Lets a
+    /// program gate an action on a quorum of off-chain guardians instead
+    /// of a single key.
+    pub fn verify_threshold(ctx: Context<Verify>, allowed: Vec<[u8; 32]>, message: Vec<u8>, threshold: u8) -> Result<()> {
+
+        // Get what should be the Ed25519Program instruction
+        let ix: Instruction = load_instruction_at_checked(0, &ctx.accounts.ix_sysvar)?;
+
+        // Check that ix carries a quorum of signatures over `message`
+        utils::verify_threshold(&ctx.accounts.ix_sysvar, &ix, &allowed, &message, threshold)?;
 
         // Do other stuff
 
@@ -42,65 +116,77 @@ pub mod utils {
     use super::*;
 
     /// Verify fields
-    pub fn sig_verify(ix: &Instruction, msg: &[u8], sig: &[u8], pubkey: &[u8]) -> ProgramResult {
+    pub fn sig_verify(ix_sysvar: &AccountInfo, ix: &Instruction, msg: &[u8], sig: &[u8], pubkey: &[u8]) -> Result<()> {
         if  ix.program_id       != ED25519_ID                   ||  // The program id we expect
-            ix.accounts.len()   != 0                            ||  // With no context accounts
-            ix.data.len()       != (16 + 64 + 32 + msg.len())       // And data of this size
+            !ix.accounts.is_empty()                             ||  // With no context accounts
+            ix.data.len()       < 16                                // At least a header
         {
             return Err(ErrorCode::SigVerificationFailed.into());    // Otherwise, we can already throw err
         }
 
-        check_data(&ix.data, msg, sig, pubkey)?;                    // If that's not the case, check data
+        check_data(ix_sysvar, &ix.data, msg, sig, pubkey)?;         // If that's not the case, check data
 
         Ok(())
     }
 
     /// Verify serialized instruction data
-    pub fn check_data(data: &[u8], msg: &[u8], sig: &[u8], pubkey: &[u8]) -> ProgramResult {
+    ///
+    /// Each of `signature_instruction_index`, `public_key_instruction_index`
+    /// and `message_instruction_index` may point at the current instruction
+    /// (`u16::MAX`, meaning the bytes are inline in `data`) or at a different
+    /// instruction in the same transaction, in which case the corresponding
+    /// bytes are fetched from *that* instruction's data via `ix_sysvar`.
+    pub fn check_data(ix_sysvar: &AccountInfo, data: &[u8], msg: &[u8], sig: &[u8], pubkey: &[u8]) -> Result<()> {
         // According to this layout used by the Ed25519Program
         // https://github.com/solana-labs/solana-web3.js/blob/d93efdf/src/ed25519-program.ts#L102
 
-        // "Deserializing" byte slices
-
-        let num_signatures                  = &[data[0]];        // Byte  0
-        let padding                         = &[data[1]];        // Byte  1
-        let signature_offset                = &data[2..=3];      // Bytes 2,3
-        let signature_instruction_index     = &data[4..=5];      // Bytes 4,5
-        let public_key_offset               = &data[6..=7];      // Bytes 6,7
-        let public_key_instruction_index    = &data[8..=9];      // Bytes 8,9
-        let message_data_offset             = &data[10..=11];    // Bytes 10,11
-        let message_data_size               = &data[12..=13];    // Bytes 12,13
-        let message_instruction_index       = &data[14..=15];    // Bytes 14,15
-
-        let data_pubkey                     = &data[16..16+32];  // Bytes 16..16+32
-        let data_sig                        = &data[48..48+64];  // Bytes 48..48+64
-        let data_msg                        = &data[112..];      // Bytes 112..end
-
-        // Expected values
-
-        let exp_public_key_offset:      u16 = 16; // 2*u8 + 7*u16
-        let exp_signature_offset:       u16 = exp_public_key_offset + pubkey.len() as u16;
-        let exp_message_data_offset:    u16 = exp_signature_offset + sig.len() as u16;
-        let exp_num_signatures:          u8 = 1;
-        let exp_message_data_size:      u16 = msg.len().try_into().unwrap();
-
-        // Header and Arg Checks
-
-        // Header
-        if  num_signatures                  != &exp_num_signatures.to_le_bytes()        ||
-            padding                         != &[0]                                     ||
-            signature_offset                != &exp_signature_offset.to_le_bytes()      ||
-            signature_instruction_index     != &u16::MAX.to_le_bytes()                  ||
-            public_key_offset               != &exp_public_key_offset.to_le_bytes()     ||
-            public_key_instruction_index    != &u16::MAX.to_le_bytes()                  ||
-            message_data_offset             != &exp_message_data_offset.to_le_bytes()   ||
-            message_data_size               != &exp_message_data_size.to_le_bytes()     ||
-            message_instruction_index       != &u16::MAX.to_le_bytes()  
+        let num_signatures = *data.first().ok_or(ErrorCode::SigVerificationFailed)?;  // Byte  0
+        let padding        = *data.get(1).ok_or(ErrorCode::SigVerificationFailed)?;   // Byte  1
+
+        let offsets: &Ed25519SignatureOffsets = bytemuck::try_from_bytes(
+            data.get(2..16).ok_or(ErrorCode::SigVerificationFailed)?
+        ).map_err(|_| ErrorCode::SigVerificationFailed)?;
+
+        let signature_offset                = offsets.signature_offset;
+        let signature_instruction_index     = offsets.signature_instruction_index;
+        let public_key_offset               = offsets.public_key_offset;
+        let public_key_instruction_index    = offsets.public_key_instruction_index;
+        let message_data_offset             = offsets.message_data_offset;
+        let message_data_size               = offsets.message_data_size;
+        let message_instruction_index       = offsets.message_instruction_index;
+
+        // Expected values for the fields that stay inline (`u16::MAX`), in
+        // the order the Ed25519Program always lays them out: pubkey, then
+        // signature, then message. Fields pointing elsewhere are not bound
+        // by this offset, since they're relative to a different instruction.
+        let mut cursor: u16 = 16; // 2*u8 + 7*u16
+        let exp_public_key_offset:   u16 = cursor;
+        if public_key_instruction_index == u16::MAX { cursor += pubkey.len() as u16; }
+        let exp_signature_offset:    u16 = cursor;
+        if signature_instruction_index == u16::MAX { cursor += sig.len() as u16; }
+        let exp_message_data_offset: u16 = cursor;
+
+        let exp_num_signatures:     u8 = 1;
+        let exp_message_data_size: u16 = msg.len().try_into().unwrap();
+
+        // Header checks
+
+        if  num_signatures != exp_num_signatures                                                                  ||
+            padding        != 0                                                                                   ||
+            message_data_size != exp_message_data_size                                                            ||
+            (public_key_instruction_index == u16::MAX  && public_key_offset   != exp_public_key_offset)           ||
+            (signature_instruction_index  == u16::MAX  && signature_offset    != exp_signature_offset)            ||
+            (message_instruction_index    == u16::MAX  && message_data_offset != exp_message_data_offset)
         {
             return Err(ErrorCode::SigVerificationFailed.into());
         }
 
-        // Arguments
+        // Arguments, fetched either from `data` or from the referenced instruction
+
+        let data_pubkey = fetch_bytes(ix_sysvar, data, public_key_instruction_index, public_key_offset, pubkey.len())?;
+        let data_sig    = fetch_bytes(ix_sysvar, data, signature_instruction_index, signature_offset, sig.len())?;
+        let data_msg    = fetch_bytes(ix_sysvar, data, message_instruction_index, message_data_offset, msg.len())?;
+
         if  data_pubkey != pubkey   ||
             data_msg    != msg      ||
             data_sig    != sig
@@ -110,6 +196,322 @@ pub mod utils {
 
         Ok(())
     }
+
+    /// Fetch `len` bytes at `offset`, either from the current instruction's
+    /// `data` (when `instruction_index` is `u16::MAX`) or from the data of
+    /// the instruction at `instruction_index` in the same transaction.
+    fn fetch_bytes(ix_sysvar: &AccountInfo, data: &[u8], instruction_index: u16, offset: u16, len: usize) -> std::result::Result<Vec<u8>, ErrorCode> {
+        let source: Vec<u8> = if instruction_index == u16::MAX {
+            data.to_vec()
+        } else {
+            load_instruction_at_checked(instruction_index as usize, ix_sysvar)
+                .map_err(|_| ErrorCode::SigVerificationFailed)?
+                .data
+        };
+
+        source.get(offset as usize..offset as usize + len)
+            .map(|bytes| bytes.to_vec())
+            .ok_or(ErrorCode::SigVerificationFailed)
+    }
+
+    /// Verify fields for an instruction carrying `sigs.len()` signatures
+    pub fn sig_verify_many(ix_sysvar: &AccountInfo, ix: &Instruction, sigs: &[(Vec<u8>, [u8; 64], [u8; 32])]) -> Result<()> {
+        let num_signatures = sigs.len();
+
+        if  ix.program_id       != ED25519_ID                ||  // The program id we expect
+            !ix.accounts.is_empty()                          ||  // With no context accounts
+            num_signatures      == 0                         ||  // With at least one signature
+            num_signatures      > 255                        ||  // And no more than the native program allows
+            ix.data.len()       < 2 + num_signatures * 14         // At least the headers
+        {
+            return Err(ErrorCode::SigVerificationFailed.into());
+        }
+
+        check_data_many(ix_sysvar, &ix.data, sigs)?;
+
+        Ok(())
+    }
+
+    /// Verify serialized instruction data carrying multiple signatures
+    ///
+    /// According to this layout used by the Ed25519Program
+    /// https://github.com/solana-labs/solana-web3.js/blob/d93efdf/src/ed25519-program.ts#L102
+    /// but repeated `num_signatures` times: byte 0 is `num_signatures`, byte 1
+    /// is padding, then `num_signatures` 14-byte offset records starting at
+    /// byte 2, followed by the pubkey/signature/message bytes themselves.
+    /// As in `check_data`, any of a record's `*_instruction_index` fields may
+    /// point at a different instruction instead of at this one.
+    pub fn check_data_many(ix_sysvar: &AccountInfo, data: &[u8], sigs: &[(Vec<u8>, [u8; 64], [u8; 32])]) -> Result<()> {
+        let num_signatures = sigs.len();
+
+        if  data.len() < 2                                  ||
+            data[0] as usize != num_signatures               ||
+            data[1]          != 0
+        {
+            return Err(ErrorCode::SigVerificationFailed.into());
+        }
+
+        // Bytes right after the last offset record, where the inline
+        // arguments of the first signature start
+        let mut cursor: u16 = (2 + num_signatures * 14).try_into().unwrap();
+
+        for (i, (msg, sig, pubkey)) in sigs.iter().enumerate() {
+            let header_start = 2 + i * 14;
+
+            let offsets: &Ed25519SignatureOffsets = bytemuck::try_from_bytes(
+                data.get(header_start..header_start + 14).ok_or(ErrorCode::SigVerificationFailed)?
+            ).map_err(|_| ErrorCode::SigVerificationFailed)?;
+
+            let signature_offset                = offsets.signature_offset;
+            let signature_instruction_index     = offsets.signature_instruction_index;
+            let public_key_offset               = offsets.public_key_offset;
+            let public_key_instruction_index    = offsets.public_key_instruction_index;
+            let message_data_offset             = offsets.message_data_offset;
+            let message_data_size               = offsets.message_data_size;
+            let message_instruction_index       = offsets.message_instruction_index;
+
+            let exp_public_key_offset:   u16 = cursor;
+            if public_key_instruction_index == u16::MAX { cursor += pubkey.len() as u16; }
+            let exp_signature_offset:    u16 = cursor;
+            if signature_instruction_index == u16::MAX { cursor += sig.len() as u16; }
+            let exp_message_data_offset: u16 = cursor;
+            let exp_message_data_size:   u16 = msg.len().try_into().unwrap();
+
+            if  message_data_size != exp_message_data_size                                                    ||
+                (public_key_instruction_index == u16::MAX && public_key_offset   != exp_public_key_offset)     ||
+                (signature_instruction_index  == u16::MAX && signature_offset    != exp_signature_offset)      ||
+                (message_instruction_index    == u16::MAX && message_data_offset != exp_message_data_offset)
+            {
+                return Err(ErrorCode::SigVerificationFailed.into());
+            }
+
+            let data_pubkey = fetch_bytes(ix_sysvar, data, public_key_instruction_index, public_key_offset, pubkey.len())?;
+            let data_sig    = fetch_bytes(ix_sysvar, data, signature_instruction_index, signature_offset, sig.len())?;
+            let data_msg    = fetch_bytes(ix_sysvar, data, message_instruction_index, message_data_offset, msg.len())?;
+
+            if  data_pubkey != pubkey           ||
+                data_msg    != msg.as_slice()   ||
+                data_sig    != sig
+            {
+                return Err(ErrorCode::SigVerificationFailed.into());
+            }
+
+            if message_instruction_index == u16::MAX { cursor += exp_message_data_size; }
+        }
+
+        Ok(())
+    }
+
+    /// Verify fields for a `secp256k1_program` instruction, the native
+    /// program Solana uses to verify Ethereum-style ECDSA signatures
+    /// against a 20-byte Ethereum address instead of a 32-byte pubkey.
+    pub fn secp256k1_verify(ix_sysvar: &AccountInfo, ix: &Instruction, msg: &[u8], sig: &[u8], eth_address: &[u8]) -> Result<()> {
+        if  ix.program_id       != SECP256K1_ID                 ||  // The program id we expect
+            !ix.accounts.is_empty()                             ||  // With no context accounts
+            ix.data.len()       < 1 + 11                            // At least the count and one record
+        {
+            return Err(ErrorCode::SigVerificationFailed.into());
+        }
+
+        check_secp_data(ix_sysvar, &ix.data, msg, sig, eth_address)?;
+
+        Ok(())
+    }
+
+    /// Verify serialized `secp256k1_program` instruction data
+    ///
+    /// According to this layout used by the Secp256k1Program
+    /// https://docs.rs/solana-program/latest/solana_program/secp256k1_program/index.html
+    /// byte 0 is `count`, then `count` 11-byte offset records:
+    /// `signature_offset` (u16), `signature_instruction_index` (u8),
+    /// `eth_address_offset` (u16), `eth_address_instruction_index` (u8),
+    /// `message_data_offset` (u16), `message_data_size` (u16),
+    /// `message_instruction_index` (u8). Like `check_data`, an
+    /// `*_instruction_index` of `u8::MAX` means the bytes are inline in
+    /// `data`; any other value means they live in a different instruction.
+    pub fn check_secp_data(ix_sysvar: &AccountInfo, data: &[u8], msg: &[u8], sig: &[u8], eth_address: &[u8]) -> Result<()> {
+        let count = *data.first().ok_or(ErrorCode::SigVerificationFailed)?;    // Byte 0
+
+        let record = data.get(1..1 + 11).ok_or(ErrorCode::SigVerificationFailed)?;
+
+        let signature_offset                = u16::from_le_bytes(record[0..=1].try_into().unwrap());
+        let signature_instruction_index     = record[2];
+        let eth_address_offset              = u16::from_le_bytes(record[3..=4].try_into().unwrap());
+        let eth_address_instruction_index   = record[5];
+        let message_data_offset             = u16::from_le_bytes(record[6..=7].try_into().unwrap());
+        let message_data_size               = u16::from_le_bytes(record[8..=9].try_into().unwrap());
+        let message_instruction_index       = record[10];
+
+        // Expected values for the fields that stay inline (`u8::MAX`), in
+        // the order the Secp256k1Program lays them out: eth address, then
+        // signature, then message.
+        let mut cursor: u16 = (1 + 11).try_into().unwrap();
+        let exp_eth_address_offset:  u16 = cursor;
+        if eth_address_instruction_index == u8::MAX { cursor += eth_address.len() as u16; }
+        let exp_signature_offset:    u16 = cursor;
+        if signature_instruction_index == u8::MAX { cursor += sig.len() as u16; }
+        let exp_message_data_offset: u16 = cursor;
+
+        let exp_count:             u8 = 1;
+        let exp_message_data_size: u16 = msg.len().try_into().unwrap();
+
+        // Header checks
+
+        if  count != exp_count                                                                                ||
+            message_data_size != exp_message_data_size                                                        ||
+            (eth_address_instruction_index == u8::MAX && eth_address_offset  != exp_eth_address_offset)       ||
+            (signature_instruction_index   == u8::MAX && signature_offset    != exp_signature_offset)         ||
+            (message_instruction_index     == u8::MAX && message_data_offset != exp_message_data_offset)
+        {
+            return Err(ErrorCode::SigVerificationFailed.into());
+        }
+
+        // Arguments, fetched either from `data` or from the referenced instruction
+
+        let data_eth_address = fetch_bytes_u8(ix_sysvar, data, eth_address_instruction_index, eth_address_offset, eth_address.len())?;
+        let data_sig         = fetch_bytes_u8(ix_sysvar, data, signature_instruction_index, signature_offset, sig.len())?;
+        let data_msg         = fetch_bytes_u8(ix_sysvar, data, message_instruction_index, message_data_offset, msg.len())?;
+
+        if  data_eth_address != eth_address    ||
+            data_msg         != msg            ||
+            data_sig         != sig
+        {
+            return Err(ErrorCode::SigVerificationFailed.into());
+        }
+
+        Ok(())
+    }
+
+    /// Same as `fetch_bytes`, but for the `secp256k1_program` layout, which
+    /// uses `u8::MAX` rather than `u16::MAX` as its "this instruction" sentinel.
+    fn fetch_bytes_u8(ix_sysvar: &AccountInfo, data: &[u8], instruction_index: u8, offset: u16, len: usize) -> std::result::Result<Vec<u8>, ErrorCode> {
+        let source: Vec<u8> = if instruction_index == u8::MAX {
+            data.to_vec()
+        } else {
+            load_instruction_at_checked(instruction_index as usize, ix_sysvar)
+                .map_err(|_| ErrorCode::SigVerificationFailed)?
+                .data
+        };
+
+        source.get(offset as usize..offset as usize + len)
+            .map(|bytes| bytes.to_vec())
+            .ok_or(ErrorCode::SigVerificationFailed)
+    }
+
+    /// Load the Ed25519Program instruction at `index` and return the
+    /// `(signer, message)` pairs it attests to, instead of requiring the
+    /// caller to already know the pubkey/message it wants to check against.
+    /// Unlike `sig_verify`, failures are reported with a specific
+    /// `ErrorCode` variant rather than the catch-all `SigVerificationFailed`.
+    pub fn parse_verified(ix_sysvar: &AccountInfo, index: u8) -> std::result::Result<Vec<(Pubkey, Vec<u8>)>, ErrorCode> {
+        let ix = load_instruction_at_checked(index as usize, ix_sysvar)
+            .map_err(|_| ErrorCode::InstructionNotFound)?;
+
+        if ix.program_id != ED25519_ID || !ix.accounts.is_empty() {
+            return Err(ErrorCode::WrongProgramId);
+        }
+
+        if ix.data.len() < 2 {
+            return Err(ErrorCode::InvalidDataSize);
+        }
+
+        let num_signatures = ix.data[0] as usize;
+        if ix.data[1] != 0 {
+            // Right length, wrong contents: the padding byte is malformed,
+            // not missing, so this belongs with the other layout violations.
+            return Err(ErrorCode::InvalidOffsets);
+        }
+
+        let headers_end = 2 + num_signatures * 14;
+        if ix.data.len() < headers_end {
+            return Err(ErrorCode::SigCountMismatch);
+        }
+
+        let mut verified = Vec::with_capacity(num_signatures);
+
+        for i in 0..num_signatures {
+            let header_start = 2 + i * 14;
+            let offsets: &Ed25519SignatureOffsets = bytemuck::try_from_bytes(
+                ix.data.get(header_start..header_start + 14).ok_or(ErrorCode::InvalidDataSize)?
+            ).map_err(|_| ErrorCode::InvalidOffsets)?;
+
+            let pubkey_bytes = fetch_bytes(ix_sysvar, &ix.data, offsets.public_key_instruction_index, offsets.public_key_offset, 32)
+                .map_err(|_| ErrorCode::InvalidOffsets)?;
+            let msg_bytes = fetch_bytes(ix_sysvar, &ix.data, offsets.message_instruction_index, offsets.message_data_offset, offsets.message_data_size as usize)
+                .map_err(|_| ErrorCode::InvalidOffsets)?;
+
+            verified.push((Pubkey::new(&pubkey_bytes), msg_bytes));
+        }
+
+        Ok(verified)
+    }
+
+    /// Confirm that the introspected Ed25519Program instruction carries at
+    /// least `threshold` distinct signatures over `message`, each from a
+    /// distinct member of `allowed` (a fixed, ordered guardian set). At most
+    /// 128 allowed keys are supported, tracked via a `seen` bitmap.
+    /// Like `parse_verified`, structural failures are reported with a
+    /// specific `ErrorCode` variant; invalid `allowed`/`threshold` arguments
+    /// and the actual verification outcomes (wrong message, unrecognized
+    /// signer, a duplicate, or an unmet threshold) fall back to the
+    /// catch-all `SigVerificationFailed`.
+    pub fn verify_threshold(ix_sysvar: &AccountInfo, ix: &Instruction, allowed: &[[u8; 32]], message: &[u8], threshold: u8) -> Result<()> {
+        if ix.program_id != ED25519_ID || !ix.accounts.is_empty() {
+            return Err(ErrorCode::WrongProgramId.into());
+        }
+
+        if  allowed.is_empty()                          ||  // With a non-empty guardian set
+            allowed.len()        > 128                  ||  // That fits in our seen-bitmap
+            threshold            == 0                   ||  // And a sensible threshold
+            threshold as usize    > allowed.len()            // that's achievable at all
+        {
+            return Err(ErrorCode::SigVerificationFailed.into());
+        }
+
+        if ix.data.len() < 2 {
+            return Err(ErrorCode::InvalidDataSize.into());
+        }
+
+        let num_signatures = ix.data[0] as usize;
+        if ix.data[1] != 0 {
+            return Err(ErrorCode::InvalidOffsets.into());
+        }
+        if ix.data.len() < 2 + num_signatures * 14 {
+            return Err(ErrorCode::SigCountMismatch.into());
+        }
+
+        // Bit `i` is set once the signature from `allowed[i]` has been seen
+        let mut seen: u128 = 0;
+
+        for i in 0..num_signatures {
+            let header_start = 2 + i * 14;
+            let offsets: &Ed25519SignatureOffsets = bytemuck::try_from_bytes(
+                ix.data.get(header_start..header_start + 14).ok_or(ErrorCode::InvalidOffsets)?
+            ).map_err(|_| ErrorCode::InvalidOffsets)?;
+
+            let data_msg = fetch_bytes(ix_sysvar, &ix.data, offsets.message_instruction_index, offsets.message_data_offset, offsets.message_data_size as usize)
+                .map_err(|_| ErrorCode::InvalidOffsets)?;
+            if data_msg != message {
+                return Err(ErrorCode::SigVerificationFailed.into());
+            }
+
+            let data_pubkey = fetch_bytes(ix_sysvar, &ix.data, offsets.public_key_instruction_index, offsets.public_key_offset, 32)
+                .map_err(|_| ErrorCode::InvalidOffsets)?;
+            let signer_index = allowed.iter().position(|pubkey| pubkey.as_slice() == data_pubkey.as_slice())
+                .ok_or(ErrorCode::SigVerificationFailed)?;
+
+            if seen & (1u128 << signer_index) != 0 {
+                return Err(ErrorCode::SigVerificationFailed.into()); // duplicate signer
+            }
+            seen |= 1u128 << signer_index;
+        }
+
+        if (seen.count_ones() as u8) < threshold {
+            return Err(ErrorCode::SigVerificationFailed.into());
+        }
+
+        Ok(())
+    }
 }
 
 /// Context accounts
@@ -126,8 +528,505 @@ pub struct Verify<'info> {
 }
 
 /// Custom error codes
-#[error]
+#[error_code]
 pub enum ErrorCode {
     #[msg("EC25519 signature verification failed.")]
     SigVerificationFailed,
+
+    #[msg("No instruction was found at the given index.")]
+    InstructionNotFound,
+
+    #[msg("The introspected instruction does not belong to the expected program or carries accounts.")]
+    WrongProgramId,
+
+    #[msg("The introspected instruction's data is too short to contain a valid header.")]
+    InvalidDataSize,
+
+    #[msg("The introspected instruction's offsets do not point to valid data.")]
+    InvalidOffsets,
+
+    #[msg("The introspected instruction does not carry the expected number of signatures.")]
+    SigCountMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::sysvar::instructions::{construct_instructions_data, BorrowedInstruction, ID as IX_SYSVAR_ID};
+
+    /// Inline Ed25519Program instruction data for a single signature whose
+    /// pubkey, signature and message are all "inline" (`u16::MAX`) — the
+    /// layout `check_data` was originally written against.
+    fn inline_ed25519_data(pubkey: &[u8; 32], sig: &[u8; 64], msg: &[u8]) -> Vec<u8> {
+        let offsets = Ed25519SignatureOffsets {
+            signature_offset: 16 + 32,
+            signature_instruction_index: u16::MAX,
+            public_key_offset: 16,
+            public_key_instruction_index: u16::MAX,
+            message_data_offset: 16 + 32 + 64,
+            message_data_size: msg.len() as u16,
+            message_instruction_index: u16::MAX,
+        };
+
+        let mut data = vec![1u8, 0u8];
+        data.extend_from_slice(bytemuck::bytes_of(&offsets));
+        data.extend_from_slice(pubkey);
+        data.extend_from_slice(sig);
+        data.extend_from_slice(msg);
+        data
+    }
+
+    /// A transaction-wide `ix_sysvar` `AccountInfo` carrying `instructions`,
+    /// used to resolve any `*_instruction_index` that isn't `u16::MAX`.
+    fn ix_sysvar_account_info<'a>(
+        key: &'a Pubkey,
+        lamports: &'a mut u64,
+        data: &'a mut [u8],
+        owner: &'a Pubkey,
+    ) -> AccountInfo<'a> {
+        AccountInfo::new(key, false, false, lamports, data, owner, false, 0)
+    }
+
+    fn build_sysvar_data(instructions_data: &[&[u8]]) -> Vec<u8> {
+        let program_id = Pubkey::new_unique();
+        let instructions: Vec<BorrowedInstruction> = instructions_data
+            .iter()
+            .map(|data| BorrowedInstruction { program_id: &program_id, accounts: vec![], data })
+            .collect();
+        construct_instructions_data(&instructions)
+    }
+
+    /// Same as `build_sysvar_data`, but instruction 0 carries `ED25519_ID`
+    /// as its program id, for tests that `load_instruction_at_checked` it
+    /// directly (e.g. `parse_verified`) instead of only referencing it.
+    fn build_sysvar_data_ed25519_first(instructions_data: &[&[u8]]) -> Vec<u8> {
+        let other_program_id = Pubkey::new_unique();
+        let instructions: Vec<BorrowedInstruction> = instructions_data
+            .iter()
+            .enumerate()
+            .map(|(i, data)| BorrowedInstruction {
+                program_id: if i == 0 { &ED25519_ID } else { &other_program_id },
+                accounts: vec![],
+                data,
+            })
+            .collect();
+        construct_instructions_data(&instructions)
+    }
+
+    #[test]
+    fn check_data_all_inline_matches_old_behavior() {
+        let pubkey = [1u8; 32];
+        let sig = [2u8; 64];
+        let msg = b"hello world".to_vec();
+        let data = inline_ed25519_data(&pubkey, &sig, &msg);
+
+        // No other instruction is ever consulted when every field is
+        // inline, so an empty sysvar is enough to prove that.
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data(&[&[]]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::check_data(&ix_sysvar, &data, &msg, &sig, &pubkey).is_ok());
+
+        let wrong_msg = b"goodbye".to_vec();
+        assert!(utils::check_data(&ix_sysvar, &data, &wrong_msg, &sig, &pubkey).is_err());
+    }
+
+    #[test]
+    fn check_data_pubkey_from_another_instruction() {
+        let pubkey = [3u8; 32];
+        let sig = [4u8; 64];
+        let msg = b"cross-ix pubkey".to_vec();
+
+        // The pubkey now lives in instruction 1's data instead of inline;
+        // sig and message stay inline, at the offsets they'd have if the
+        // pubkey weren't occupying any space in `data`.
+        let offsets = Ed25519SignatureOffsets {
+            signature_offset: 16,
+            signature_instruction_index: u16::MAX,
+            public_key_offset: 0,
+            public_key_instruction_index: 1,
+            message_data_offset: 16 + 64,
+            message_data_size: msg.len() as u16,
+            message_instruction_index: u16::MAX,
+        };
+        let mut data = vec![1u8, 0u8];
+        data.extend_from_slice(bytemuck::bytes_of(&offsets));
+        data.extend_from_slice(&sig);
+        data.extend_from_slice(&msg);
+
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data(&[&[], &pubkey]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::check_data(&ix_sysvar, &data, &msg, &sig, &pubkey).is_ok());
+
+        let wrong_pubkey = [9u8; 32];
+        assert!(utils::check_data(&ix_sysvar, &data, &msg, &sig, &wrong_pubkey).is_err());
+    }
+
+    #[test]
+    fn check_data_signature_from_another_instruction() {
+        let pubkey = [5u8; 32];
+        let sig = [6u8; 64];
+        let msg = b"cross-ix signature".to_vec();
+
+        let offsets = Ed25519SignatureOffsets {
+            signature_offset: 0,
+            signature_instruction_index: 1,
+            public_key_offset: 16,
+            public_key_instruction_index: u16::MAX,
+            message_data_offset: 16 + 32,
+            message_data_size: msg.len() as u16,
+            message_instruction_index: u16::MAX,
+        };
+        let mut data = vec![1u8, 0u8];
+        data.extend_from_slice(bytemuck::bytes_of(&offsets));
+        data.extend_from_slice(&pubkey);
+        data.extend_from_slice(&msg);
+
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data(&[&[], &sig]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::check_data(&ix_sysvar, &data, &msg, &sig, &pubkey).is_ok());
+
+        let wrong_sig = [7u8; 64];
+        assert!(utils::check_data(&ix_sysvar, &data, &msg, &wrong_sig, &pubkey).is_err());
+    }
+
+    #[test]
+    fn check_data_message_from_another_instruction() {
+        let pubkey = [8u8; 32];
+        let sig = [9u8; 64];
+        let msg = b"cross-ix message".to_vec();
+
+        let offsets = Ed25519SignatureOffsets {
+            signature_offset: 16 + 32,
+            signature_instruction_index: u16::MAX,
+            public_key_offset: 16,
+            public_key_instruction_index: u16::MAX,
+            message_data_offset: 0,
+            message_data_size: msg.len() as u16,
+            message_instruction_index: 1,
+        };
+        let mut data = vec![1u8, 0u8];
+        data.extend_from_slice(bytemuck::bytes_of(&offsets));
+        data.extend_from_slice(&pubkey);
+        data.extend_from_slice(&sig);
+
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data(&[&[], msg.as_slice()]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::check_data(&ix_sysvar, &data, &msg, &sig, &pubkey).is_ok());
+
+        let wrong_msg = b"not the signed message".to_vec();
+        assert!(utils::check_data(&ix_sysvar, &data, &wrong_msg, &sig, &pubkey).is_err());
+    }
+
+    #[test]
+    fn check_data_truncated_header_fails_closed_without_panicking() {
+        let pubkey = [0u8; 32];
+        let sig = [0u8; 64];
+        let msg = b"short".to_vec();
+
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data(&[&[]]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        // Empty, just the count byte, and a header cut off mid-offsets —
+        // all shorter than the 16 bytes `check_data` needs to parse.
+        for data in [vec![], vec![1u8], vec![1u8, 0u8, 2, 3, 4]] {
+            assert!(utils::check_data(&ix_sysvar, &data, &msg, &sig, &pubkey).is_err());
+        }
+    }
+
+    /// Build inline Ed25519Program instruction data carrying one signature
+    /// entry per `pubkeys[i]`, each attesting to the same `message`. Only
+    /// the pubkey/message offsets matter to `verify_threshold`, so the
+    /// signature field of each header is left unused.
+    fn build_threshold_ix_data(pubkeys: &[[u8; 32]], message: &[u8]) -> Vec<u8> {
+        let num_signatures = pubkeys.len();
+        let header_len = 14;
+        let headers_start = 2;
+        let body_start = headers_start + num_signatures * header_len;
+
+        let mut body = Vec::new();
+        let mut pubkey_offsets = Vec::with_capacity(num_signatures);
+        for pubkey in pubkeys {
+            pubkey_offsets.push((body_start + body.len()) as u16);
+            body.extend_from_slice(pubkey);
+        }
+        let message_offset = (body_start + body.len()) as u16;
+        body.extend_from_slice(message);
+
+        let mut data = vec![0u8; body_start];
+        data[0] = num_signatures as u8;
+        data[1] = 0;
+
+        for (i, pubkey_offset) in pubkey_offsets.iter().enumerate() {
+            let offsets = Ed25519SignatureOffsets {
+                signature_offset: 0,
+                signature_instruction_index: u16::MAX,
+                public_key_offset: *pubkey_offset,
+                public_key_instruction_index: u16::MAX,
+                message_data_offset: message_offset,
+                message_data_size: message.len() as u16,
+                message_instruction_index: u16::MAX,
+            };
+            let header_start = headers_start + i * header_len;
+            data[header_start..header_start + header_len].copy_from_slice(bytemuck::bytes_of(&offsets));
+        }
+
+        data.extend_from_slice(&body);
+        data
+    }
+
+    fn threshold_ix_sysvar() -> (Pubkey, u64, Vec<u8>, Pubkey) {
+        (IX_SYSVAR_ID, 0u64, build_sysvar_data(&[&[]]), Pubkey::default())
+    }
+
+    #[test]
+    fn verify_threshold_met_by_distinct_signers() {
+        let allowed = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        let message = b"quorum message".to_vec();
+        let data = build_threshold_ix_data(&[allowed[0], allowed[1]], &message);
+        let ix = Instruction { program_id: ED25519_ID, accounts: vec![], data };
+
+        let (key, mut lamports, mut sysvar_data, owner) = threshold_ix_sysvar();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::verify_threshold(&ix_sysvar, &ix, &allowed, &message, 2).is_ok());
+    }
+
+    #[test]
+    fn verify_threshold_rejects_duplicate_signer() {
+        let allowed = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        let message = b"quorum message".to_vec();
+        // Same signer twice instead of two distinct ones: must not count
+        // as 2 toward the threshold.
+        let data = build_threshold_ix_data(&[allowed[0], allowed[0]], &message);
+        let ix = Instruction { program_id: ED25519_ID, accounts: vec![], data };
+
+        let (key, mut lamports, mut sysvar_data, owner) = threshold_ix_sysvar();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::verify_threshold(&ix_sysvar, &ix, &allowed, &message, 2).is_err());
+    }
+
+    #[test]
+    fn verify_threshold_fails_closed_on_unknown_signer() {
+        let allowed = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        let outsider = [9u8; 32];
+        let message = b"quorum message".to_vec();
+        let data = build_threshold_ix_data(&[allowed[0], outsider], &message);
+        let ix = Instruction { program_id: ED25519_ID, accounts: vec![], data };
+
+        let (key, mut lamports, mut sysvar_data, owner) = threshold_ix_sysvar();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::verify_threshold(&ix_sysvar, &ix, &allowed, &message, 2).is_err());
+    }
+
+    #[test]
+    fn verify_threshold_enforces_the_count() {
+        let allowed = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        let message = b"quorum message".to_vec();
+        // 2-of-3 required, but only one valid signature is present.
+        let data = build_threshold_ix_data(&[allowed[0]], &message);
+        let ix = Instruction { program_id: ED25519_ID, accounts: vec![], data };
+
+        let (key, mut lamports, mut sysvar_data, owner) = threshold_ix_sysvar();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::verify_threshold(&ix_sysvar, &ix, &allowed, &message, 2).is_err());
+    }
+
+    #[test]
+    fn check_secp_data_truncated_header_fails_closed_without_panicking() {
+        let sig = [0u8; 65];
+        let eth_address = [0u8; 20];
+        let msg = b"short".to_vec();
+
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data(&[&[]]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        // Empty, just the count byte, and a record cut off mid-offsets —
+        // all shorter than the 12 bytes `check_secp_data` needs to parse.
+        for data in [vec![], vec![1u8], vec![1u8, 2, 3, 4, 5]] {
+            assert!(utils::check_secp_data(&ix_sysvar, &data, &msg, &sig, &eth_address).is_err());
+        }
+    }
+
+    /// Build the shared header-table layout `check_data_many` and
+    /// `parse_verified` both parse: `headers.len()` as byte 0, a zero
+    /// padding byte, then each 14-byte offsets header back to back,
+    /// followed by whatever inline bytes `body` holds.
+    fn build_headers_and_body(headers: &[Ed25519SignatureOffsets], body: &[u8]) -> Vec<u8> {
+        let header_len = 14;
+        let headers_start = 2;
+        let mut data = vec![0u8; headers_start + headers.len() * header_len];
+        data[0] = headers.len() as u8;
+        data[1] = 0;
+
+        for (i, offsets) in headers.iter().enumerate() {
+            let header_start = headers_start + i * header_len;
+            data[header_start..header_start + header_len].copy_from_slice(bytemuck::bytes_of(offsets));
+        }
+
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn check_data_many_all_inline_happy_path() {
+        let sigs: Vec<(Vec<u8>, [u8; 64], [u8; 32])> = vec![
+            (b"first message".to_vec(), [1u8; 64], [2u8; 32]),
+            (b"second message".to_vec(), [3u8; 64], [4u8; 32]),
+        ];
+
+        let headers_start = 2 + sigs.len() * 14;
+        let mut cursor = headers_start as u16;
+        let mut body = Vec::new();
+        let mut headers = Vec::with_capacity(sigs.len());
+        for (msg, sig, pubkey) in &sigs {
+            let public_key_offset = cursor;
+            cursor += pubkey.len() as u16;
+            let signature_offset = cursor;
+            cursor += sig.len() as u16;
+            let message_data_offset = cursor;
+            cursor += msg.len() as u16;
+
+            headers.push(Ed25519SignatureOffsets {
+                signature_offset,
+                signature_instruction_index: u16::MAX,
+                public_key_offset,
+                public_key_instruction_index: u16::MAX,
+                message_data_offset,
+                message_data_size: msg.len() as u16,
+                message_instruction_index: u16::MAX,
+            });
+            body.extend_from_slice(pubkey);
+            body.extend_from_slice(sig);
+            body.extend_from_slice(msg);
+        }
+        let data = build_headers_and_body(&headers, &body);
+
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data(&[&[]]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::check_data_many(&ix_sysvar, &data, &sigs).is_ok());
+    }
+
+    #[test]
+    fn check_data_many_pubkey_from_another_instruction() {
+        let pubkey = [5u8; 32];
+        let sig = [6u8; 64];
+        let msg = b"cross-ix many".to_vec();
+        let sigs: Vec<(Vec<u8>, [u8; 64], [u8; 32])> = vec![(msg.clone(), sig, pubkey)];
+
+        // Signature and message stay inline right after the single header;
+        // the pubkey is fetched from instruction 1 instead.
+        let inline_start = 2 + 14;
+        let headers = vec![Ed25519SignatureOffsets {
+            signature_offset: inline_start as u16,
+            signature_instruction_index: u16::MAX,
+            public_key_offset: 0,
+            public_key_instruction_index: 1,
+            message_data_offset: (inline_start + sig.len()) as u16,
+            message_data_size: msg.len() as u16,
+            message_instruction_index: u16::MAX,
+        }];
+        let mut body = Vec::new();
+        body.extend_from_slice(&sig);
+        body.extend_from_slice(&msg);
+        let data = build_headers_and_body(&headers, &body);
+
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data(&[&[], &pubkey]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        assert!(utils::check_data_many(&ix_sysvar, &data, &sigs).is_ok());
+
+        let wrong_sigs: Vec<(Vec<u8>, [u8; 64], [u8; 32])> = vec![(msg, sig, [7u8; 32])];
+        assert!(utils::check_data_many(&ix_sysvar, &data, &wrong_sigs).is_err());
+    }
+
+    #[test]
+    fn parse_verified_all_inline_happy_path() {
+        let pubkey = [8u8; 32];
+        let msg = b"parsed message".to_vec();
+
+        let inline_start = 2 + 14;
+        let headers = vec![Ed25519SignatureOffsets {
+            signature_offset: 0,
+            signature_instruction_index: u16::MAX,
+            public_key_offset: inline_start as u16,
+            public_key_instruction_index: u16::MAX,
+            message_data_offset: (inline_start + pubkey.len()) as u16,
+            message_data_size: msg.len() as u16,
+            message_instruction_index: u16::MAX,
+        }];
+        let mut body = Vec::new();
+        body.extend_from_slice(&pubkey);
+        body.extend_from_slice(&msg);
+        let data = build_headers_and_body(&headers, &body);
+
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data_ed25519_first(&[data.as_slice()]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        let verified = utils::parse_verified(&ix_sysvar, 0).unwrap();
+        assert_eq!(verified, vec![(Pubkey::new(&pubkey), msg)]);
+    }
+
+    #[test]
+    fn parse_verified_message_from_another_instruction() {
+        let pubkey = [9u8; 32];
+        let msg = b"cross-ix parsed message".to_vec();
+
+        let inline_start = 2 + 14;
+        let headers = vec![Ed25519SignatureOffsets {
+            signature_offset: 0,
+            signature_instruction_index: u16::MAX,
+            public_key_offset: inline_start as u16,
+            public_key_instruction_index: u16::MAX,
+            message_data_offset: 0,
+            message_data_size: msg.len() as u16,
+            message_instruction_index: 1,
+        }];
+        let mut body = Vec::new();
+        body.extend_from_slice(&pubkey);
+        let ed25519_ix_data = build_headers_and_body(&headers, &body);
+
+        let key = IX_SYSVAR_ID;
+        let mut lamports = 0u64;
+        let mut sysvar_data = build_sysvar_data_ed25519_first(&[&ed25519_ix_data, msg.as_slice()]);
+        let owner = Pubkey::default();
+        let ix_sysvar = ix_sysvar_account_info(&key, &mut lamports, &mut sysvar_data, &owner);
+
+        let verified = utils::parse_verified(&ix_sysvar, 0).unwrap();
+        assert_eq!(verified, vec![(Pubkey::new(&pubkey), msg)]);
+    }
 }